@@ -0,0 +1,44 @@
+//! Errors.
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+use core::fmt;
+
+/// An error that occurred while encoding a value to DAG-CBOR.
+#[derive(Debug)]
+pub enum EncodeError<E> {
+    /// An error occurred while writing to the underlying writer.
+    Write(E),
+    /// An error with a custom message, used for DAG-CBOR conformance violations that don't map
+    /// onto a writer error (e.g. non-finite floats, out-of-range integers, malformed keys).
+    Msg(String),
+    /// The value being serialized nested more deeply than the serializer's configured
+    /// `max_depth`.
+    DepthLimit,
+}
+
+impl<E> From<E> for EncodeError<E> {
+    fn from(err: E) -> Self {
+        EncodeError::Write(err)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for EncodeError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::Write(err) => write!(f, "write error: {err}"),
+            EncodeError::Msg(msg) => write!(f, "{msg}"),
+            EncodeError::DepthLimit => write!(f, "exceeded the maximum nesting depth"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: std::error::Error + 'static> std::error::Error for EncodeError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EncodeError::Write(err) => Some(err),
+            EncodeError::Msg(_) | EncodeError::DepthLimit => None,
+        }
+    }
+}
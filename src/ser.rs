@@ -14,6 +14,7 @@ use cbor4ii::core::{
 use serde::Serialize;
 
 use crate::error::EncodeError;
+use crate::tags;
 
 /// Serializes a value to a vector.
 pub fn to_vec<T>(value: &T) -> Result<Vec<u8>, EncodeError<TryReserveError>>
@@ -37,15 +38,117 @@ where
     value.serialize(&mut serializer)
 }
 
+/// Canonical ordering used to sort map/struct keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyOrder {
+    /// The order mandated by the IPLD DAG-CBOR specification: keys whose encoded byte
+    /// representation is shorter sort first; equal-length keys are compared bytewise. This is
+    /// the default, since it's what makes output actually DAG-CBOR conformant.
+    #[default]
+    LengthFirst,
+    /// Pure bytewise lexicographic order, per RFC 8949 §4.2.1 core deterministic encoding.
+    /// Useful for interop with plain canonical CBOR rather than DAG-CBOR.
+    Bytewise,
+}
+
+impl KeyOrder {
+    fn compare(self, a: &[u8], b: &[u8]) -> core::cmp::Ordering {
+        match self {
+            KeyOrder::LengthFirst => a.len().cmp(&b.len()).then_with(|| a.cmp(b)),
+            KeyOrder::Bytewise => a.cmp(b),
+        }
+    }
+}
+
+/// The default limit on how deeply nested a value may be before serialization is aborted. See
+/// [`Serializer::max_depth`].
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
 /// A structure for serializing Rust values to DAG-CBOR.
 pub struct Serializer<W> {
     writer: W,
+    /// When `true`, map and struct keys are trusted to already arrive in canonical order: they
+    /// are written directly to the underlying writer instead of being buffered and sorted.
+    assume_sorted_keys: bool,
+    /// The canonical ordering used to sort buffered map/struct entries.
+    key_order: KeyOrder,
+    /// How many levels of seq/tuple/map/struct nesting are currently open.
+    depth: usize,
+    /// The maximum nesting depth a value may reach before serialization is aborted.
+    max_depth: usize,
+    /// When `true`, map keys are required to be CBOR text strings with no duplicates, per the
+    /// DAG-CBOR specification.
+    strict_keys: bool,
 }
 
 impl<W> Serializer<W> {
     /// Creates a new CBOR serializer.
     pub fn new(writer: W) -> Serializer<W> {
-        Serializer { writer }
+        Serializer {
+            writer,
+            assume_sorted_keys: false,
+            key_order: KeyOrder::default(),
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
+            strict_keys: false,
+        }
+    }
+
+    /// Creates a serializer for a scratch buffer used to encode a single key or value nested
+    /// inside this one (e.g. a map/struct entry, or an unknown-length seq's counting buffer).
+    ///
+    /// Inherits `key_order`, `strict_keys`, and the *current* `depth`/`max_depth`, so that a map
+    /// nested inside a value sorts its entries and validates its keys the same way the
+    /// outermost map does, and recursion through collection-boundary scratch buffers is still
+    /// bounded by the original depth budget instead of each buffer silently starting a fresh one.
+    fn nested<W2>(&self, writer: W2) -> Serializer<W2> {
+        Serializer {
+            writer,
+            assume_sorted_keys: false,
+            key_order: self.key_order,
+            depth: self.depth,
+            max_depth: self.max_depth,
+            strict_keys: self.strict_keys,
+        }
+    }
+
+    /// Selects the canonical key ordering used when sorting buffered map/struct entries.
+    /// Defaults to [`KeyOrder::LengthFirst`]; use [`KeyOrder::Bytewise`] for plain RFC 8949
+    /// canonical CBOR interop instead of DAG-CBOR conformance.
+    pub fn key_order(mut self, key_order: KeyOrder) -> Self {
+        self.key_order = key_order;
+        self
+    }
+
+    /// Puts the serializer into (or out of) unbuffered "pre-sorted keys" map mode.
+    ///
+    /// In this mode `CollectMap` skips buffering and sorting entries: it writes the map/struct
+    /// header up front and encodes each key and value directly to the underlying writer as they
+    /// arrive, trusting the caller to emit keys already in canonical order (and verifying that
+    /// they strictly increase as it goes, by recycling two scratch buffers rather than
+    /// allocating per key). This allows full documents to be serialized with no per-key heap
+    /// allocation, pairing well with [`crate::writer::SliceWriter`] for `no_std`/embedded use.
+    /// Maps serialized in this mode must have a known length.
+    pub fn assume_sorted_keys(mut self, assume_sorted_keys: bool) -> Self {
+        self.assume_sorted_keys = assume_sorted_keys;
+        self
+    }
+
+    /// Sets the maximum nesting depth (across seqs, tuples, maps, and structs, including their
+    /// variant forms) a value may reach before serialization is aborted with
+    /// [`EncodeError::DepthLimit`]. Defaults to [`DEFAULT_MAX_DEPTH`]. Bounds resource use when
+    /// encoding attacker-controlled data.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Enables strict DAG-CBOR map-key validation: every map key must serialize as a CBOR text
+    /// string, and duplicate keys are rejected, instead of silently producing nonconformant
+    /// output.
+    pub fn strict_keys(mut self, strict_keys: bool) -> Self {
+        self.strict_keys = strict_keys;
+        self
     }
 
     /// Returns the underlying writer.
@@ -54,6 +157,23 @@ impl<W> Serializer<W> {
     }
 }
 
+impl<W: enc::Write> Serializer<W> {
+    /// Enters a level of seq/tuple/map/struct nesting, erroring if doing so would exceed
+    /// `max_depth`.
+    fn enter_depth(&mut self) -> Result<(), EncodeError<W::Error>> {
+        if self.depth >= self.max_depth {
+            return Err(EncodeError::DepthLimit);
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Leaves a level of nesting entered via `enter_depth`.
+    fn exit_depth(&mut self) {
+        self.depth -= 1;
+    }
+}
+
 impl<'a, W: enc::Write> serde::Serializer for &'a mut Serializer<W> {
     type Ok = ();
     type Error = EncodeError<W::Error>;
@@ -165,7 +285,13 @@ impl<'a, W: enc::Write> serde::Serializer for &'a mut Serializer<W> {
 
     #[inline]
     fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error> {
-        value.serialize(self)
+        // `Some` forwards transparently to its inner value rather than going through a
+        // collection constructor, but it can still recurse arbitrarily deep (e.g.
+        // `Option<Box<Self>>`), so it must count against the depth budget too.
+        self.enter_depth()?;
+        let result = value.serialize(&mut *self);
+        self.exit_depth();
+        result
     }
 
     #[inline]
@@ -194,10 +320,45 @@ impl<'a, W: enc::Write> serde::Serializer for &'a mut Serializer<W> {
     #[inline]
     fn serialize_newtype_struct<T: Serialize + ?Sized>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error> {
-        value.serialize(self)
+        // DAG-CBOR permits exactly one CBOR tag: tag 42, used for CID links. `Cid`/`Link`
+        // smuggle themselves through as a newtype struct with a reserved, NUL-prefixed name so
+        // we can intercept them here instead of serializing them transparently.
+        if name == tags::CID_SENTINEL_NAME {
+            let cid_bytes = value.serialize(tags::CidBytesSerializer).map_err(|_| {
+                EncodeError::Msg("CID/Link inner value must serialize as a byte string".to_string())
+            })?;
+
+            // Tag 42 header (major type 6, value 42).
+            self.writer.push(&[0xD8, 0x2A])?;
+
+            // DAG-CBOR links are a byte string: a leading `0x00` multibase identity byte
+            // followed by the raw CID bytes.
+            let mut link_bytes = Vec::with_capacity(cid_bytes.len() + 1);
+            link_bytes.push(0);
+            link_bytes.extend_from_slice(&cid_bytes);
+            types::Bytes(&link_bytes).encode(&mut self.writer)?;
+
+            return Ok(());
+        }
+
+        if tags::is_reserved_sentinel(name) {
+            return Err(EncodeError::Msg(
+                "Only tag 42 (CID links) is supported in DAG-CBOR; other reserved tag sentinels \
+                 are not"
+                    .to_string(),
+            ));
+        }
+
+        // A plain newtype struct forwards transparently to its inner value, so it can recurse
+        // arbitrarily deep (e.g. `struct Wrapper(Box<Wrapper>)`) without going through a
+        // collection constructor; count it against the depth budget too.
+        self.enter_depth()?;
+        let result = value.serialize(&mut *self);
+        self.exit_depth();
+        result
     }
 
     #[inline]
@@ -208,18 +369,25 @@ impl<'a, W: enc::Write> serde::Serializer for &'a mut Serializer<W> {
         variant: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error> {
+        // Encoded as a single-entry map, so it's nesting like any other collection, just without
+        // going through the shared `CollectMap`/`CollectSeq` constructors that normally guard it.
+        self.enter_depth()?;
         enc::MapStartBounded(1).encode(&mut self.writer)?;
         variant.encode(&mut self.writer)?;
-        value.serialize(self)
+        let result = value.serialize(&mut *self);
+        self.exit_depth();
+        result
     }
 
     #[inline]
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        self.enter_depth()?;
         CollectSeq::new(self, len)
     }
 
     #[inline]
     fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.enter_depth()?;
         enc::ArrayStartBounded(len).encode(&mut self.writer)?;
         Ok(BoundedCollect { ser: self })
     }
@@ -241,6 +409,7 @@ impl<'a, W: enc::Write> serde::Serializer for &'a mut Serializer<W> {
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        self.enter_depth()?;
         enc::MapStartBounded(1).encode(&mut self.writer)?;
         variant.encode(&mut self.writer)?;
         enc::ArrayStartBounded(len).encode(&mut self.writer)?;
@@ -248,7 +417,18 @@ impl<'a, W: enc::Write> serde::Serializer for &'a mut Serializer<W> {
     }
 
     #[inline]
-    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        self.enter_depth()?;
+        if self.assume_sorted_keys {
+            // Unbuffered mode writes the header up front, so it needs to know the length now.
+            let len = len.ok_or_else(|| {
+                EncodeError::Msg(
+                    "Maps must have a known length to use the unbuffered pre-sorted-keys mode"
+                        .to_string(),
+                )
+            })?;
+            enc::MapStartBounded(len).encode(&mut self.writer)?;
+        }
         Ok(CollectMap::new(self))
     }
 
@@ -258,6 +438,7 @@ impl<'a, W: enc::Write> serde::Serializer for &'a mut Serializer<W> {
         _name: &'static str,
         len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.enter_depth()?;
         enc::MapStartBounded(len).encode(&mut self.writer)?;
         Ok(CollectMap::new(self))
     }
@@ -270,6 +451,7 @@ impl<'a, W: enc::Write> serde::Serializer for &'a mut Serializer<W> {
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        self.enter_depth()?;
         enc::MapStartBounded(1).encode(&mut self.writer)?;
         variant.encode(&mut self.writer)?;
         enc::MapStartBounded(len).encode(&mut self.writer)?;
@@ -323,7 +505,7 @@ impl<'a, W: enc::Write> CollectSeq<'a, W> {
             enc::ArrayStartBounded(len).encode(&mut ser.writer)?;
             None
         } else {
-            Some(Serializer::new(BufWriter::new(Vec::new())))
+            Some(ser.nested(BufWriter::new(Vec::new())))
         };
         Ok(Self {
             count: 0,
@@ -363,6 +545,7 @@ impl<W: enc::Write> serde::ser::SerializeSeq for CollectSeq<'_, W> {
             self.ser.writer.push(&ser.into_inner().into_inner())?;
         }
 
+        self.ser.exit_depth();
         Ok(())
     }
 }
@@ -378,6 +561,7 @@ impl<W: enc::Write> serde::ser::SerializeTuple for BoundedCollect<'_, W> {
 
     #[inline]
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.ser.exit_depth();
         Ok(())
     }
 }
@@ -393,6 +577,7 @@ impl<W: enc::Write> serde::ser::SerializeTupleStruct for BoundedCollect<'_, W> {
 
     #[inline]
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.ser.exit_depth();
         Ok(())
     }
 }
@@ -408,18 +593,75 @@ impl<W: enc::Write> serde::ser::SerializeTupleVariant for BoundedCollect<'_, W>
 
     #[inline]
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.ser.exit_depth();
         Ok(())
     }
 }
 
 /// CBOR RFC-8949 specifies a canonical sort order, where keys are sorted in bytewise
-/// lexicographic order. We serialize keys and values separately, then sort by key bytes only.
-/// Once sorted, the key-value pairs are written to the actual output.
+/// lexicographic order. By default we serialize keys and values separately, then sort by key
+/// bytes only, using the serializer's [`KeyOrder`] (length-first unless set to
+/// [`KeyOrder::Bytewise`]). Once sorted, the key-value pairs are written to the actual output.
+///
+/// When the serializer is in `assume_sorted_keys` mode (see [`Serializer::assume_sorted_keys`]),
+/// entries are not buffered at all: each key and value is encoded directly to the underlying
+/// writer as it arrives, trusting the caller to emit keys already in canonical order. Only the
+/// key bytes are kept around in that mode, in order to verify that ordering holds.
 pub struct CollectMap<'a, W> {
-    key_buffer: BufWriter,
-    value_buffer: BufWriter,
-    entries: Vec<(Vec<u8>, Vec<u8>)>, // (key_bytes, value_bytes)
     ser: &'a mut Serializer<W>,
+    mode: CollectMapMode,
+}
+
+/// Returns `true` if `key_bytes` starts with a CBOR major-type-3 (text string) initial byte, as
+/// required for strict DAG-CBOR map keys.
+fn is_text_string_key(key_bytes: &[u8]) -> bool {
+    matches!(key_bytes.first(), Some(0x60..=0x7B))
+}
+
+/// Converts an error from serializing a map key or value into one for the map's own writer:
+/// [`EncodeError::DepthLimit`] is passed through unchanged, anything else becomes `msg`.
+fn entry_error<E, E2>(err: EncodeError<E>, msg: &str) -> EncodeError<E2> {
+    match err {
+        EncodeError::DepthLimit => EncodeError::DepthLimit,
+        _ => EncodeError::Msg(msg.to_string()),
+    }
+}
+
+enum CollectMapMode {
+    /// Every key and value is appended to a pair of shared, growable arenas instead of one
+    /// `Vec<u8>` per entry. `entries` records `(key_start, key_len, value_start, value_len)`
+    /// byte-range offsets into `key_arena`/`value_arena`; once `end` is called, the offsets are
+    /// sorted by comparing the referenced key byte ranges and the ranges are flushed to the
+    /// writer in that order. This keeps output identical to sorting owned `Vec<u8>` entries
+    /// while cutting per-entry allocations from two down to the arenas' amortized growth.
+    Buffered {
+        key_arena: BufWriter,
+        value_arena: BufWriter,
+        /// The start offset into `key_arena` of the key currently being assembled by
+        /// `serialize_key`, consumed by the matching `serialize_value` call.
+        pending_key_start: usize,
+        entries: Vec<(usize, usize, usize, usize)>,
+    },
+    /// Keys and values are written straight to the underlying writer. `key_buffer` is reused
+    /// scratch space for capturing a key's bytes so they can be compared against `previous_key`.
+    /// `previous_key` is itself a recycled buffer (see `recycle_key_buffer`), not a copy, so
+    /// verifying order costs no per-key heap allocation.
+    Unbuffered {
+        key_buffer: BufWriter,
+        previous_key: Option<BufWriter>,
+    },
+}
+
+/// Moves `key_buffer`'s bytes into `previous_key` for the next call's order check, and hands back
+/// `previous_key`'s old (now-cleared) buffer as the new `key_buffer` — so capturing each key to
+/// compare against the last costs no per-key heap allocation, only the buffers' amortized growth.
+fn recycle_key_buffer(key_buffer: &mut BufWriter, previous_key: &mut Option<BufWriter>) {
+    let mut next_key_buffer = previous_key
+        .take()
+        .unwrap_or_else(|| BufWriter::new(Vec::new()));
+    next_key_buffer.clear();
+    core::mem::swap(key_buffer, &mut next_key_buffer);
+    *previous_key = Some(next_key_buffer);
 }
 
 impl<'a, W> CollectMap<'a, W>
@@ -427,12 +669,20 @@ where
     W: enc::Write,
 {
     fn new(ser: &'a mut Serializer<W>) -> Self {
-        Self {
-            key_buffer: BufWriter::new(Vec::new()),
-            value_buffer: BufWriter::new(Vec::new()),
-            entries: Vec::new(),
-            ser,
-        }
+        let mode = if ser.assume_sorted_keys {
+            CollectMapMode::Unbuffered {
+                key_buffer: BufWriter::new(Vec::new()),
+                previous_key: None,
+            }
+        } else {
+            CollectMapMode::Buffered {
+                key_arena: BufWriter::new(Vec::new()),
+                value_arena: BufWriter::new(Vec::new()),
+                pending_key_start: 0,
+                entries: Vec::new(),
+            }
+        };
+        Self { ser, mode }
     }
 
     fn serialize<T: Serialize + ?Sized>(
@@ -440,39 +690,132 @@ where
         maybe_key: Option<&'static str>,
         value: &T,
     ) -> Result<(), EncodeError<W::Error>> {
-        // Serialize the key separately
-        let key_bytes = if let Some(key) = maybe_key {
-            let mut key_serializer = Serializer::new(&mut self.key_buffer);
-            key.serialize(&mut key_serializer)
-                .map_err(|_| EncodeError::Msg("Struct key cannot be serialized.".to_string()))?;
-            let key_bytes = self.key_buffer.buffer().to_vec();
-            self.key_buffer.clear();
-            key_bytes
-        } else {
-            Vec::new()
-        };
-
-        // Serialize the value separately
-        let mut value_serializer = Serializer::new(&mut self.value_buffer);
-        value
-            .serialize(&mut value_serializer)
-            .map_err(|_| EncodeError::Msg("Struct value cannot be serialized.".to_string()))?;
-        let value_bytes = self.value_buffer.buffer().to_vec();
-        self.value_buffer.clear();
-
-        self.entries.push((key_bytes, value_bytes));
-
-        Ok(())
+        match &mut self.mode {
+            CollectMapMode::Buffered {
+                key_arena,
+                value_arena,
+                entries,
+                ..
+            } => {
+                let key_start = key_arena.buffer().len();
+                if let Some(key) = maybe_key {
+                    let mut key_serializer = self.ser.nested(&mut *key_arena);
+                    key.serialize(&mut key_serializer).map_err(|_| {
+                        EncodeError::Msg("Struct key cannot be serialized.".to_string())
+                    })?;
+                }
+                let key_len = key_arena.buffer().len() - key_start;
+
+                if self.ser.strict_keys
+                    && maybe_key.is_some()
+                    && !is_text_string_key(&key_arena.buffer()[key_start..key_start + key_len])
+                {
+                    return Err(EncodeError::Msg(
+                        "Map keys must be CBOR text strings in strict mode".to_string(),
+                    ));
+                }
+
+                let value_start = value_arena.buffer().len();
+                let mut value_serializer = self.ser.nested(&mut *value_arena);
+                value.serialize(&mut value_serializer).map_err(|err| {
+                    entry_error(err, "Struct value cannot be serialized.")
+                })?;
+                let value_len = value_arena.buffer().len() - value_start;
+
+                entries.push((key_start, key_len, value_start, value_len));
+                Ok(())
+            }
+            CollectMapMode::Unbuffered {
+                key_buffer,
+                previous_key,
+            } => {
+                key_buffer.clear();
+                if let Some(key) = maybe_key {
+                    let mut key_serializer = self.ser.nested(&mut *key_buffer);
+                    key.serialize(&mut key_serializer).map_err(|_| {
+                        EncodeError::Msg("Struct key cannot be serialized.".to_string())
+                    })?;
+                }
+
+                if self.ser.strict_keys
+                    && maybe_key.is_some()
+                    && !is_text_string_key(key_buffer.buffer())
+                {
+                    return Err(EncodeError::Msg(
+                        "Map keys must be CBOR text strings in strict mode".to_string(),
+                    ));
+                }
+
+                if let Some(prev) = previous_key.as_ref() {
+                    if self
+                        .ser
+                        .key_order
+                        .compare(key_buffer.buffer(), prev.buffer())
+                        != core::cmp::Ordering::Greater
+                    {
+                        return Err(EncodeError::Msg(
+                            "Map keys must be written in strictly increasing canonical order \
+                             when assume_sorted_keys is enabled"
+                                .to_string(),
+                        ));
+                    }
+                }
+
+                self.ser.writer.push(key_buffer.buffer())?;
+                value.serialize(&mut *self.ser).map_err(|err| {
+                    entry_error(err, "Struct value cannot be serialized.")
+                })?;
+                recycle_key_buffer(key_buffer, previous_key);
+                Ok(())
+            }
+        }
     }
 
-    fn end(mut self) -> Result<(), EncodeError<W::Error>> {
-        // Sort entries by key bytes only in lexicographic order per RFC 8949
-        self.entries.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+    fn end(self) -> Result<(), EncodeError<W::Error>> {
+        let CollectMap { ser, mode } = self;
+
+        let result = match mode {
+            CollectMapMode::Buffered {
+                key_arena,
+                value_arena,
+                mut entries,
+                pending_key_start: _,
+            } => {
+                // Sort the offset tuples by comparing the key byte ranges they reference,
+                // per the serializer's configured `KeyOrder`, rather than sorting owned bytes.
+                let key_order = ser.key_order;
+                let key_bytes = key_arena.buffer();
+                let key_range = |start: usize, len: usize| &key_bytes[start..start + len];
+                entries.sort_unstable_by(|a, b| {
+                    key_order.compare(key_range(a.0, a.1), key_range(b.0, b.1))
+                });
+
+                if ser.strict_keys
+                    && entries.windows(2).any(|pair| {
+                        key_range(pair[0].0, pair[0].1) == key_range(pair[1].0, pair[1].1)
+                    })
+                {
+                    Err(EncodeError::Msg(
+                        "Duplicate map keys are not allowed in strict mode".to_string(),
+                    ))
+                } else {
+                    let value_bytes = value_arena.buffer();
+                    entries.into_iter().try_for_each(
+                        |(key_start, key_len, value_start, value_len)| {
+                            ser.writer.push(key_range(key_start, key_len))?;
+                            ser.writer
+                                .push(&value_bytes[value_start..value_start + value_len])
+                        },
+                    )
+                }
+            }
+            // Everything was already written to the underlying writer as it arrived; the header
+            // was written up front in `serialize_map`/`serialize_struct`/`serialize_struct_variant`.
+            CollectMapMode::Unbuffered { .. } => Ok(()),
+        };
 
-        for (key_bytes, value_bytes) in self.entries {
-            self.ser.writer.push(&key_bytes)?;
-            self.ser.writer.push(&value_bytes)?;
-        }
+        ser.exit_depth();
+        result?;
         Ok(())
     }
 }
@@ -486,34 +829,97 @@ where
 
     #[inline]
     fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
-        // Serialize the key into the key buffer
-        let mut key_serializer = Serializer::new(&mut self.key_buffer);
-        key.serialize(&mut key_serializer)
-            .map_err(|_| EncodeError::Msg("Map key cannot be serialized.".to_string()))?;
-        Ok(())
+        match &mut self.mode {
+            CollectMapMode::Buffered {
+                key_arena,
+                pending_key_start,
+                ..
+            } => {
+                *pending_key_start = key_arena.buffer().len();
+                let mut key_serializer = self.ser.nested(&mut *key_arena);
+                key.serialize(&mut key_serializer)
+                    .map_err(|err| entry_error(err, "Map key cannot be serialized."))?;
+                Ok(())
+            }
+            CollectMapMode::Unbuffered { key_buffer, .. } => {
+                let mut key_serializer = self.ser.nested(&mut *key_buffer);
+                key.serialize(&mut key_serializer)
+                    .map_err(|err| entry_error(err, "Map key cannot be serialized."))?;
+                Ok(())
+            }
+        }
     }
 
     #[inline]
     fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
-        // Serialize the value into the value buffer
-        let mut value_serializer = Serializer::new(&mut self.value_buffer);
-        value
-            .serialize(&mut value_serializer)
-            .map_err(|_| EncodeError::Msg("Map value cannot be serialized.".to_string()))?;
-
-        // Now store both key and value bytes as a pair
-        let key_bytes = self.key_buffer.buffer().to_vec();
-        let value_bytes = self.value_buffer.buffer().to_vec();
-        self.key_buffer.clear();
-        self.value_buffer.clear();
-
-        self.entries.push((key_bytes, value_bytes));
-        Ok(())
+        match &mut self.mode {
+            CollectMapMode::Buffered {
+                key_arena,
+                value_arena,
+                entries,
+                pending_key_start,
+            } => {
+                let key_start = *pending_key_start;
+                let key_len = key_arena.buffer().len() - key_start;
+
+                if self.ser.strict_keys
+                    && !is_text_string_key(&key_arena.buffer()[key_start..key_start + key_len])
+                {
+                    return Err(EncodeError::Msg(
+                        "Map keys must be CBOR text strings in strict mode".to_string(),
+                    ));
+                }
+
+                let value_start = value_arena.buffer().len();
+                let mut value_serializer = self.ser.nested(&mut *value_arena);
+                value
+                    .serialize(&mut value_serializer)
+                    .map_err(|err| entry_error(err, "Map value cannot be serialized."))?;
+                let value_len = value_arena.buffer().len() - value_start;
+
+                entries.push((key_start, key_len, value_start, value_len));
+                Ok(())
+            }
+            CollectMapMode::Unbuffered {
+                key_buffer,
+                previous_key,
+            } => {
+                if self.ser.strict_keys && !is_text_string_key(key_buffer.buffer()) {
+                    return Err(EncodeError::Msg(
+                        "Map keys must be CBOR text strings in strict mode".to_string(),
+                    ));
+                }
+
+                if let Some(prev) = previous_key.as_ref() {
+                    if self
+                        .ser
+                        .key_order
+                        .compare(key_buffer.buffer(), prev.buffer())
+                        != core::cmp::Ordering::Greater
+                    {
+                        return Err(EncodeError::Msg(
+                            "Map keys must be written in strictly increasing canonical order \
+                             when assume_sorted_keys is enabled"
+                                .to_string(),
+                        ));
+                    }
+                }
+
+                self.ser.writer.push(key_buffer.buffer())?;
+                value
+                    .serialize(&mut *self.ser)
+                    .map_err(|err| entry_error(err, "Map value cannot be serialized."))?;
+                recycle_key_buffer(key_buffer, previous_key);
+                Ok(())
+            }
+        }
     }
 
     #[inline]
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        enc::MapStartBounded(self.entries.len()).encode(&mut self.ser.writer)?;
+        if let CollectMapMode::Buffered { entries, .. } = &self.mode {
+            enc::MapStartBounded(entries.len()).encode(&mut self.ser.writer)?;
+        }
         self.end()
     }
 }
@@ -561,3 +967,188 @@ where
         self.end()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde::ser::SerializeMap;
+
+    use super::*;
+    use crate::tags::Cid;
+
+    /// Serializes as a map containing a single entry whose value is another instance of itself,
+    /// `depth` levels deep, bottoming out in `null`. Used to check that the depth guard bounds
+    /// recursion through nested maps rather than resetting at each collection boundary.
+    struct Nested(usize);
+
+    impl Serialize for Nested {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            if self.0 == 0 {
+                serializer.serialize_none()
+            } else {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("a", &Nested(self.0 - 1))?;
+                map.end()
+            }
+        }
+    }
+
+    /// Serializes as `Some(inner)`, recursing `depth` levels deep, bottoming out in `None`. Used
+    /// to check that the depth guard also bounds recursion forwarded transparently through
+    /// `Option`, not just through collection constructors.
+    struct NestedOption(usize);
+
+    impl Serialize for NestedOption {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            if self.0 == 0 {
+                serializer.serialize_none()
+            } else {
+                serializer.serialize_some(&NestedOption(self.0 - 1))
+            }
+        }
+    }
+
+    /// Serializes as a two-entry map with a duplicate `"a"` key.
+    struct DuplicateKeys;
+
+    impl Serialize for DuplicateKeys {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut map = serializer.serialize_map(Some(2))?;
+            map.serialize_entry("a", &1)?;
+            map.serialize_entry("a", &2)?;
+            map.end()
+        }
+    }
+
+    /// Serializes as a single-entry map whose key is an integer rather than a text string.
+    struct NonStringKey;
+
+    impl Serialize for NonStringKey {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut map = serializer.serialize_map(Some(1))?;
+            map.serialize_entry(&1u32, &"v")?;
+            map.end()
+        }
+    }
+
+    /// Serializes as a two-entry map with keys `"b"` and `"aa"`. Both orderings compare the
+    /// *encoded* key bytes, and a CBOR text-string header is `0x60 + len`, so a shorter text key
+    /// always has a smaller head byte than a longer one — `LengthFirst` and `Bytewise` agree on
+    /// every all-text-key map, `"b"` (`[0x61, 0x62]`) sorting before `"aa"` (`[0x62, 0x61, 0x61]`)
+    /// under both.
+    struct TwoKeys;
+
+    impl Serialize for TwoKeys {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut map = serializer.serialize_map(Some(2))?;
+            map.serialize_entry("b", &1)?;
+            map.serialize_entry("aa", &2)?;
+            map.end()
+        }
+    }
+
+    /// Serializes as a two-entry map with a boolean key and a text key, so the orderings
+    /// genuinely diverge: `true` encodes to the single byte `0xF5` (major type 7), which is
+    /// *shorter* than `"a"`'s two bytes (`0x61, 0x61`) but bytewise *greater* than its leading
+    /// `0x61`. Only CBOR, not DAG-CBOR, allows non-text map keys; this exists purely to exercise
+    /// the `KeyOrder` comparator itself.
+    struct BoolAndTextKey;
+
+    impl Serialize for BoolAndTextKey {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut map = serializer.serialize_map(Some(2))?;
+            map.serialize_entry(&true, &1)?;
+            map.serialize_entry("a", &2)?;
+            map.end()
+        }
+    }
+
+    #[test]
+    fn cid_serializes_as_tag_42() {
+        let bytes = to_vec(&Cid(&[1, 2, 3, 4])).unwrap();
+        // Tag 42 header, then a 5-byte byte string: a 0x00 multibase identity prefix followed
+        // by the 4 raw CID bytes.
+        assert_eq!(bytes, [0xD8, 0x2A, 0x45, 0x00, 0x01, 0x02, 0x03, 0x04]);
+    }
+
+    #[test]
+    fn depth_limit_applies_to_maps_nested_through_values() {
+        // Each `Nested` level serializes its inner value through a fresh scratch-buffer
+        // sub-serializer; the guard must still catch recursion that crosses those boundaries.
+        let err = to_vec(&Nested(DEFAULT_MAX_DEPTH + 1)).unwrap_err();
+        assert!(matches!(err, EncodeError::DepthLimit));
+    }
+
+    #[test]
+    fn nested_maps_within_the_depth_budget_succeed() {
+        assert!(to_vec(&Nested(DEFAULT_MAX_DEPTH - 1)).is_ok());
+    }
+
+    #[test]
+    fn depth_limit_applies_through_option_forwarding() {
+        // `serialize_some` forwards transparently rather than going through a collection
+        // constructor; it must still count against the depth budget.
+        let err = to_vec(&NestedOption(DEFAULT_MAX_DEPTH + 1)).unwrap_err();
+        assert!(matches!(err, EncodeError::DepthLimit));
+    }
+
+    #[test]
+    fn strict_keys_rejects_duplicate_keys() {
+        let mut serializer = Serializer::new(BufWriter::new(Vec::new())).strict_keys(true);
+        assert!(DuplicateKeys.serialize(&mut serializer).is_err());
+    }
+
+    #[test]
+    fn strict_keys_rejects_non_string_keys() {
+        let mut serializer = Serializer::new(BufWriter::new(Vec::new())).strict_keys(true);
+        assert!(NonStringKey.serialize(&mut serializer).is_err());
+    }
+
+    #[test]
+    fn non_strict_mode_allows_duplicate_and_non_string_keys() {
+        let mut serializer = Serializer::new(BufWriter::new(Vec::new()));
+        assert!(DuplicateKeys.serialize(&mut serializer).is_ok());
+
+        let mut serializer = Serializer::new(BufWriter::new(Vec::new()));
+        assert!(NonStringKey.serialize(&mut serializer).is_ok());
+    }
+
+    #[test]
+    fn key_order_length_first_sorts_shorter_keys_first() {
+        let mut serializer = Serializer::new(BufWriter::new(Vec::new()));
+        TwoKeys.serialize(&mut serializer).unwrap();
+        let bytes = serializer.into_inner().into_inner();
+
+        // Map header (2 entries), then "b" => 1, then "aa" => 2.
+        assert_eq!(bytes, [0xA2, 0x61, b'b', 0x01, 0x62, b'a', b'a', 0x02]);
+    }
+
+    #[test]
+    fn key_order_bytewise_sorts_lexicographically() {
+        let mut serializer =
+            Serializer::new(BufWriter::new(Vec::new())).key_order(KeyOrder::Bytewise);
+        TwoKeys.serialize(&mut serializer).unwrap();
+        let bytes = serializer.into_inner().into_inner();
+
+        // Map header (2 entries), then "b" => 1, then "aa" => 2: as with `LengthFirst`, since
+        // text keys' encoded length order and bytewise order always agree.
+        assert_eq!(bytes, [0xA2, 0x61, b'b', 0x01, 0x62, b'a', b'a', 0x02]);
+    }
+
+    #[test]
+    fn key_order_length_first_and_bytewise_diverge_across_major_types() {
+        let mut length_first = Serializer::new(BufWriter::new(Vec::new()));
+        BoolAndTextKey.serialize(&mut length_first).unwrap();
+        let length_first_bytes = length_first.into_inner().into_inner();
+
+        // Map header (2 entries), then `true` (shorter encoding) => 1, then "a" => 2.
+        assert_eq!(length_first_bytes, [0xA2, 0xF5, 0x01, 0x61, b'a', 0x02]);
+
+        let mut bytewise =
+            Serializer::new(BufWriter::new(Vec::new())).key_order(KeyOrder::Bytewise);
+        BoolAndTextKey.serialize(&mut bytewise).unwrap();
+        let bytewise_bytes = bytewise.into_inner().into_inner();
+
+        // Map header (2 entries), then "a" (smaller leading byte) => 2, then `true` => 1.
+        assert_eq!(bytewise_bytes, [0xA2, 0x61, b'a', 0x02, 0xF5, 0x01]);
+    }
+}
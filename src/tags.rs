@@ -0,0 +1,231 @@
+//! CBOR tag support.
+//!
+//! DAG-CBOR permits exactly one CBOR tag: tag 42, used to embed IPLD CIDs ("links") inside a
+//! document. [`Cid`] is a thin wrapper that, when serialized, causes [`Serializer`](crate::ser::Serializer)
+//! to emit the tag-42 byte sequence instead of transparently forwarding to the inner value.
+#[cfg(not(feature = "std"))]
+use alloc::{string::ToString, vec::Vec};
+use core::convert::Infallible;
+
+use serde::ser::Impossible;
+use serde::Serialize;
+
+use crate::error::EncodeError;
+
+/// The reserved newtype-struct name used to recognize CID links during serialization.
+///
+/// Mirrors the sentinel-newtype technique `serde_cbor`'s `tags.rs` uses to smuggle tag
+/// information through serde's `Serialize` trait: the leading NUL byte can't appear in an
+/// ordinary Rust struct name, so this name can never collide with a real user type.
+pub(crate) const CID_SENTINEL_NAME: &str = "\0cid";
+
+/// Returns `true` if `name` looks like a reserved tag sentinel, whether or not it's one we
+/// actually support.
+pub(crate) fn is_reserved_sentinel(name: &str) -> bool {
+    name.starts_with('\0')
+}
+
+/// An IPLD CID, serialized as a DAG-CBOR tag-42 link.
+///
+/// Wrap the raw, binary CID bytes (e.g. the output of `Cid::to_bytes()` in the `cid` crate) in
+/// this type to have them encoded as `0xD8 0x2A` followed by a byte string containing the CID
+/// bytes prefixed with a single `0x00` multibase identity byte, per the DAG-CBOR specification.
+pub struct Cid<'a>(pub &'a [u8]);
+
+/// Alias for [`Cid`], matching the "link" terminology used by the IPLD data model.
+pub type Link<'a> = Cid<'a>;
+
+impl Serialize for Cid<'_> {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_newtype_struct(CID_SENTINEL_NAME, &RawBytes(self.0))
+    }
+}
+
+/// Serializes as a CBOR byte string. Used to carry the CID bytes through
+/// `serialize_newtype_struct` so the serializer can pick them back up.
+struct RawBytes<'a>(&'a [u8]);
+
+impl Serialize for RawBytes<'_> {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+/// Captures the bytes passed to `serialize_bytes`, rejecting any other shape.
+///
+/// Used to validate that a [`Cid`]'s inner value actually serializes as `&[u8]` before the
+/// tag-42 header is written.
+pub(crate) struct CidBytesSerializer;
+
+impl CidBytesSerializer {
+    fn unsupported() -> EncodeError<Infallible> {
+        EncodeError::Msg("CID/Link inner value must serialize as a byte string".to_string())
+    }
+}
+
+macro_rules! unsupported_scalar {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(
+            #[inline]
+            fn $method(self, _v: $ty) -> Result<Self::Ok, Self::Error> {
+                Err(Self::unsupported())
+            }
+        )*
+    };
+}
+
+impl serde::Serializer for CidBytesSerializer {
+    type Ok = Vec<u8>;
+    type Error = EncodeError<Infallible>;
+
+    type SerializeSeq = Impossible<Vec<u8>, Self::Error>;
+    type SerializeTuple = Impossible<Vec<u8>, Self::Error>;
+    type SerializeTupleStruct = Impossible<Vec<u8>, Self::Error>;
+    type SerializeTupleVariant = Impossible<Vec<u8>, Self::Error>;
+    type SerializeMap = Impossible<Vec<u8>, Self::Error>;
+    type SerializeStruct = Impossible<Vec<u8>, Self::Error>;
+    type SerializeStructVariant = Impossible<Vec<u8>, Self::Error>;
+
+    unsupported_scalar!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+    );
+
+    #[inline]
+    fn serialize_str(self, _v: &str) -> Result<Self::Ok, Self::Error> {
+        Err(Self::unsupported())
+    }
+
+    #[inline]
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Ok(v.to_vec())
+    }
+
+    #[inline]
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Err(Self::unsupported())
+    }
+
+    #[inline]
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<Self::Ok, Self::Error> {
+        Err(Self::unsupported())
+    }
+
+    #[inline]
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Err(Self::unsupported())
+    }
+
+    #[inline]
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Err(Self::unsupported())
+    }
+
+    #[inline]
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Err(Self::unsupported())
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        Err(Self::unsupported())
+    }
+
+    #[inline]
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(Self::unsupported())
+    }
+
+    #[inline]
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(Self::unsupported())
+    }
+
+    #[inline]
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(Self::unsupported())
+    }
+
+    #[inline]
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(Self::unsupported())
+    }
+
+    #[inline]
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(Self::unsupported())
+    }
+
+    #[inline]
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(Self::unsupported())
+    }
+
+    #[inline]
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(Self::unsupported())
+    }
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,112 @@
+//! Zero-allocation serialization into a caller-provided buffer.
+use core::fmt;
+
+use cbor4ii::core::enc;
+use serde::Serialize;
+
+use crate::error::EncodeError;
+use crate::ser::Serializer;
+
+/// Writes encoded bytes into a caller-provided `&'a mut [u8]`, tracking a write cursor so that
+/// no heap allocation is required.
+///
+/// Mirrors the `SliceWriter`/`bytes_written` pattern used by `cbor-smol` and the
+/// `encode_into_slice` convenience from `bincode`. Pairs well with
+/// [`to_slice`] for `no_std`/embedded use.
+pub struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> SliceWriter<'a> {
+    /// Creates a new `SliceWriter` writing into `buf`, starting at offset `0`.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        SliceWriter { buf, pos: 0 }
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn bytes_written(&self) -> usize {
+        self.pos
+    }
+
+    /// Consumes the writer, returning the underlying slice it was created from.
+    pub fn into_inner(self) -> &'a mut [u8] {
+        self.buf
+    }
+}
+
+impl enc::Write for SliceWriter<'_> {
+    type Error = BufferFull;
+
+    #[inline]
+    fn push(&mut self, input: &[u8]) -> Result<(), Self::Error> {
+        let remaining = self.buf.len() - self.pos;
+        if input.len() > remaining {
+            return Err(BufferFull { written: self.pos });
+        }
+
+        let end = self.pos + input.len();
+        self.buf[self.pos..end].copy_from_slice(input);
+        self.pos = end;
+        Ok(())
+    }
+}
+
+/// Returned by [`SliceWriter`] when the destination buffer does not have enough remaining space
+/// to hold the next chunk of encoded bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferFull {
+    /// The number of bytes that had already been written to the buffer before it filled up.
+    pub written: usize,
+}
+
+impl fmt::Display for BufferFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "buffer is full after {} bytes were written",
+            self.written
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BufferFull {}
+
+/// Serializes `value` into `buf` with no heap allocation, returning the number of bytes written.
+///
+/// Scalars, byte strings, known-length tuples, and fixed arrays serialize directly into `buf`.
+/// Maps and structs still require an internal buffer to sort their entries into canonical
+/// order: `to_slice` always uses the serializer's default, buffered map mode. A [`Serializer`]
+/// over a [`SliceWriter`] put into unbuffered "pre-sorted keys" map mode (see
+/// [`Serializer::assume_sorted_keys`]) can produce a full DAG-CBOR document with no per-key heap
+/// allocation: order verification recycles two scratch buffers across keys instead of copying
+/// each one.
+pub fn to_slice<T>(value: &T, buf: &mut [u8]) -> Result<usize, EncodeError<BufferFull>>
+where
+    T: Serialize + ?Sized,
+{
+    let mut serializer = Serializer::new(SliceWriter::new(buf));
+    value.serialize(&mut serializer)?;
+    Ok(serializer.into_inner().bytes_written())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::EncodeError;
+
+    #[test]
+    fn to_slice_writes_into_the_provided_buffer() {
+        let mut buf = [0u8; 16];
+        let n = to_slice(&1u8, &mut buf).unwrap();
+        assert_eq!(&buf[..n], [0x01]);
+    }
+
+    #[test]
+    fn to_slice_reports_buffer_full_when_the_value_does_not_fit() {
+        let mut buf = [0u8; 1];
+        let err = to_slice(&"hello", &mut buf).unwrap_err();
+        assert!(matches!(err, EncodeError::Write(BufferFull { .. })));
+    }
+}